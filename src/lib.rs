@@ -0,0 +1,577 @@
+use std::str::FromStr;
+
+use num::Complex;
+use rand::Rng;
+use rayon::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+// number of rows handed to a single rayon task; small enough to balance
+// load across cores, large enough to keep per-task overhead low.
+const BAND_HEIGHT: usize = 8;
+
+// the family of escape-time fractals this crate can render, selected via
+// the optional FRACTAL argument on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FractalKind {
+    Mandelbrot,
+    Multibrot3,
+    BurningShip,
+}
+
+impl FromStr for FractalKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mandelbrot" => Ok(FractalKind::Mandelbrot),
+            "multibrot3" => Ok(FractalKind::Multibrot3),
+            "burning-ship" => Ok(FractalKind::BurningShip),
+            _ => Err(format!("unknown fractal kind '{s}'")),
+        }
+    }
+}
+
+pub fn render_set(
+    pixels: &mut [u8],
+    bounds: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    limit: usize,
+    fractal: FractalKind,
+) {
+    assert_eq!(pixels.len(), bounds.0 * bounds.1);
+
+    for row in 0..bounds.1 {
+        for col in 0..bounds.0 {
+            let point = pixel_to_point(bounds, (col, row), upper_left, lower_right);
+            pixels[row * bounds.0 + col] = match escape_time(point, limit, fractal) {
+                None => 0,
+                Some(time) => 255 - time as u8,
+            };
+        }
+    }
+}
+
+// same contract as `render_set`, but the image is split into horizontal
+// bands of `BAND_HEIGHT` rows that are rendered concurrently. bands are
+// disjoint slices of `pixels`, so there's no aliasing between tasks. every
+// pixel's point is still computed from the original `bounds`/`upper_left`/
+// `lower_right` and its absolute row -- never from a shrunk per-band
+// viewport, which would reintroduce rounding that can disagree with the
+// serial path at band boundaries. this is what makes the output
+// byte-identical to `render_set`.
+pub fn render_set_parallel(
+    pixels: &mut [u8],
+    bounds: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    limit: usize,
+    fractal: FractalKind,
+) {
+    assert_eq!(pixels.len(), bounds.0 * bounds.1);
+
+    let band_rows = bounds.0 * BAND_HEIGHT;
+    pixels
+        .par_chunks_mut(band_rows)
+        .enumerate()
+        .for_each(|(i, band)| {
+            let top = i * BAND_HEIGHT;
+            let height = band.len() / bounds.0;
+            for local_row in 0..height {
+                let row = top + local_row;
+                for col in 0..bounds.0 {
+                    let point = pixel_to_point(bounds, (col, row), upper_left, lower_right);
+                    band[local_row * bounds.0 + col] = match escape_time(point, limit, fractal) {
+                        None => 0,
+                        Some(time) => 255 - time as u8,
+                    };
+                }
+            }
+        });
+}
+
+// like `render_set`, but instead of a grayscale byte every pixel takes up
+// 3 bytes of `pixels`, colored via `smooth_color`.
+pub fn render_set_color(
+    pixels: &mut [u8],
+    bounds: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    limit: usize,
+    fractal: FractalKind,
+) {
+    assert_eq!(pixels.len(), bounds.0 * bounds.1 * 3);
+
+    for row in 0..bounds.1 {
+        for col in 0..bounds.0 {
+            let point = pixel_to_point(bounds, (col, row), upper_left, lower_right);
+            let offset = (row * bounds.0 + col) * 3;
+            let rgb = match escape_time_detailed(point, limit, fractal) {
+                None => [0, 0, 0],
+                Some((time, norm_sqr)) => smooth_color(time, norm_sqr),
+            };
+            pixels[offset..offset + 3].copy_from_slice(&rgb);
+        }
+    }
+}
+
+// same contract as `render_set_color`, banded across rayon tasks like
+// `render_set_parallel`; the band stride is 3x wider to account for RGB.
+// like `render_set_parallel`, every pixel's point is computed from the
+// original viewport and its absolute row, not a per-band one.
+pub fn render_set_color_parallel(
+    pixels: &mut [u8],
+    bounds: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    limit: usize,
+    fractal: FractalKind,
+) {
+    assert_eq!(pixels.len(), bounds.0 * bounds.1 * 3);
+
+    let band_rows = bounds.0 * BAND_HEIGHT * 3;
+    pixels
+        .par_chunks_mut(band_rows)
+        .enumerate()
+        .for_each(|(i, band)| {
+            let top = i * BAND_HEIGHT;
+            let height = band.len() / (bounds.0 * 3);
+            for local_row in 0..height {
+                let row = top + local_row;
+                for col in 0..bounds.0 {
+                    let point = pixel_to_point(bounds, (col, row), upper_left, lower_right);
+                    let offset = (local_row * bounds.0 + col) * 3;
+                    let rgb = match escape_time_detailed(point, limit, fractal) {
+                        None => [0, 0, 0],
+                        Some((time, norm_sqr)) => smooth_color(time, norm_sqr),
+                    };
+                    band[offset..offset + 3].copy_from_slice(&rgb);
+                }
+            }
+        });
+}
+
+// renders a Buddhabrot instead of the usual escape-time image: samples
+// random points `c` in the view region, and for every sample whose orbit
+// *escapes* within `limit` iterations, walks the orbit again and bumps a
+// hit-counter at every pixel it passes through. points that never escape
+// (interior orbits) are discarded entirely, and orbit points that land
+// outside the view are simply skipped.
+//
+// samples are farmed out across rayon tasks like the escape-time
+// renderers: each task accumulates into its own hit buffer (`fold`), and
+// the buffers are summed together at the end (`reduce`). the orbit buffer
+// is reused across samples within a task instead of being reallocated for
+// every one, including the (common) ones that never escape.
+pub fn render_buddhabrot(
+    bounds: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    samples: usize,
+    limit: usize,
+) -> Vec<u8> {
+    let pixel_count = bounds.0 * bounds.1;
+
+    let hits = (0..samples)
+        .into_par_iter()
+        .fold(
+            || (vec![0u32; pixel_count], Vec::with_capacity(limit)),
+            |(mut hits, mut orbit), _| {
+                let mut rng = rand::thread_rng();
+                let c = Complex {
+                    re: rng.gen_range(upper_left.re..lower_right.re),
+                    im: rng.gen_range(lower_right.im..upper_left.im),
+                };
+
+                orbit.clear();
+                let mut z = Complex { re: 0.0, im: 0.0 };
+                let mut escaped = false;
+                for _ in 0..limit {
+                    if z.norm_sqr() > 4.0 {
+                        escaped = true;
+                        break;
+                    }
+                    orbit.push(z);
+                    z = z * z + c;
+                }
+
+                if escaped {
+                    for &point in orbit.iter() {
+                        if let Some((col, row)) = point_to_pixel(bounds, point, upper_left, lower_right) {
+                            hits[row * bounds.0 + col] += 1;
+                        }
+                    }
+                }
+
+                (hits, orbit)
+            },
+        )
+        .map(|(hits, _orbit)| hits)
+        .reduce(
+            || vec![0u32; pixel_count],
+            |mut total, partial| {
+                for (t, p) in total.iter_mut().zip(partial) {
+                    *t += p;
+                }
+                total
+            },
+        );
+
+    normalize_hits(&hits)
+}
+
+// linearly rescales a hit-count buffer into a grayscale byte buffer, with
+// the brightest pixel mapped to 255.
+fn normalize_hits(hits: &[u32]) -> Vec<u8> {
+    let max = hits.iter().copied().max().unwrap_or(0);
+    if max == 0 {
+        return vec![0; hits.len()];
+    }
+
+    hits.iter()
+        .map(|&hit| (hit as f64 / max as f64 * 255.0) as u8)
+        .collect()
+}
+
+#[test]
+fn test_normalize_hits_scales_to_255() {
+    assert_eq!(normalize_hits(&[0, 0, 0]), vec![0, 0, 0]);
+    assert_eq!(normalize_hits(&[0, 5, 10]), vec![0, 127, 255]);
+}
+
+#[test]
+fn test_render_buddhabrot_produces_right_sized_buffer() {
+    let bounds = (20, 15);
+    let upper_left = Complex { re: -2.0, im: 1.5 };
+    let lower_right = Complex { re: 1.0, im: -1.5 };
+
+    let image = render_buddhabrot(bounds, upper_left, lower_right, 2000, 50);
+
+    assert_eq!(image.len(), bounds.0 * bounds.1);
+}
+
+// maps a renormalized escape count to an RGB triple by walking a cyclic
+// sinusoidal gradient, phase-shifted per channel so the palette doesn't
+// band the way a single grayscale ramp does.
+fn smooth_color(time: usize, escape_norm_sqr: f64) -> [u8; 3] {
+    const SCALE: f64 = 0.05;
+
+    let ln_z = 0.5 * escape_norm_sqr.ln();
+    let nu =
+        time as f64 + 1.0 - (ln_z / std::f64::consts::LN_2).ln() / std::f64::consts::LN_2;
+    let t = nu * SCALE * std::f64::consts::TAU;
+
+    let channel = |phase: f64| (0.5 + 0.5 * (t + phase).sin()) * 255.0;
+    [
+        channel(0.0) as u8,
+        channel(std::f64::consts::TAU / 3.0) as u8,
+        channel(2.0 * std::f64::consts::TAU / 3.0) as u8,
+    ]
+}
+
+#[test]
+fn test_smooth_color() {
+    assert_eq!(smooth_color(3, 4.5), [247, 105, 29]);
+    assert_eq!(smooth_color(10, 4.0001), [88, 42, 252]);
+}
+
+// this function checks whether a given complex number `c`
+// belongs to the `fractal` set using at most `limit` iterations.
+//
+// if `c` belongs to the set, it returns `None`.
+// otherwise, the function returns `Some(i)` where `i` is the number of iterations
+// it took `c` to escape the circle of radius 2.
+pub fn escape_time(c: Complex<f64>, limit: usize, fractal: FractalKind) -> Option<usize> {
+    escape_time_detailed(c, limit, fractal).map(|(time, _)| time)
+}
+
+// same contract as `escape_time`, but on escape it also returns `|z|^2` at
+// the moment of escape, which smooth coloring needs to interpolate between
+// iteration counts.
+fn escape_time_detailed(
+    c: Complex<f64>,
+    limit: usize,
+    fractal: FractalKind,
+) -> Option<(usize, f64)> {
+    let mut z = Complex { re: 0.0, im: 0.0 };
+    for i in 0..limit {
+        let norm_sqr = z.norm_sqr();
+        if norm_sqr > 4.0 {
+            return Some((i, norm_sqr));
+        }
+        z = match fractal {
+            FractalKind::Mandelbrot => z * z + c,
+            FractalKind::Multibrot3 => z * z * z + c,
+            FractalKind::BurningShip => {
+                let folded = Complex {
+                    re: z.re.abs(),
+                    im: z.im.abs(),
+                };
+                folded * folded + c
+            }
+        };
+    }
+
+    None
+}
+
+#[test]
+fn test_escape_time() {
+    let m = FractalKind::Mandelbrot;
+    assert_eq!(escape_time(Complex { re: 0.0, im: 0.0 }, 10, m), None);
+    assert_eq!(escape_time(Complex { re: 0.25, im: 0.0 }, 10, m), None);
+    assert_eq!(escape_time(Complex { re: 0.5, im: 0.0 }, 10, m), Some(5));
+    assert_eq!(escape_time(Complex { re: 1.0, im: 0.0 }, 10, m), Some(3));
+    assert_eq!(escape_time(Complex { re: 0.0, im: 0.25 }, 10, m), None);
+    assert_eq!(escape_time(Complex { re: 0.0, im: 0.5 }, 10, m), None);
+    assert_eq!(escape_time(Complex { re: 0.0, im: 1.0 }, 10, m), None);
+}
+
+#[test]
+fn test_fractal_kind_from_str() {
+    assert_eq!(FractalKind::from_str("mandelbrot"), Ok(FractalKind::Mandelbrot));
+    assert_eq!(FractalKind::from_str("multibrot3"), Ok(FractalKind::Multibrot3));
+    assert_eq!(FractalKind::from_str("burning-ship"), Ok(FractalKind::BurningShip));
+    assert!(FractalKind::from_str("nonsense").is_err());
+}
+
+#[test]
+fn test_render_set_parallel_matches_serial() {
+    let bounds = (30, 17);
+    let upper_left = Complex { re: -1.20, im: 0.35 };
+    let lower_right = Complex { re: -1.0, im: 0.20 };
+    let fractal = FractalKind::Mandelbrot;
+
+    let mut serial = vec![0; bounds.0 * bounds.1];
+    render_set(&mut serial, bounds, upper_left, lower_right, 255, fractal);
+
+    let mut parallel = vec![0; bounds.0 * bounds.1];
+    render_set_parallel(&mut parallel, bounds, upper_left, lower_right, 255, fractal);
+
+    assert_eq!(serial, parallel);
+}
+
+// a deep zoom crossing many band boundaries: this is the region a prior
+// version of `render_set_parallel` got wrong by re-deriving each band's
+// viewport via `pixel_to_point` instead of reusing the original one, which
+// introduced rounding that disagreed with the serial path at band seams.
+#[test]
+fn test_render_set_parallel_matches_serial_on_deep_zoom() {
+    let bounds = (101, 79);
+    let upper_left = Complex {
+        re: -0.743644786,
+        im: 0.1318259042,
+    };
+    let lower_right = Complex {
+        re: -0.743643786,
+        im: 0.1318249042,
+    };
+    let fractal = FractalKind::Mandelbrot;
+
+    let mut serial = vec![0; bounds.0 * bounds.1];
+    render_set(&mut serial, bounds, upper_left, lower_right, 5000, fractal);
+
+    let mut parallel = vec![0; bounds.0 * bounds.1];
+    render_set_parallel(&mut parallel, bounds, upper_left, lower_right, 5000, fractal);
+
+    assert_eq!(serial, parallel);
+}
+
+#[test]
+fn test_render_set_color_parallel_matches_serial() {
+    let bounds = (30, 17);
+    let upper_left = Complex { re: -1.20, im: 0.35 };
+    let lower_right = Complex { re: -1.0, im: 0.20 };
+    let fractal = FractalKind::Mandelbrot;
+
+    let mut serial = vec![0; bounds.0 * bounds.1 * 3];
+    render_set_color(&mut serial, bounds, upper_left, lower_right, 255, fractal);
+
+    let mut parallel = vec![0; bounds.0 * bounds.1 * 3];
+    render_set_color_parallel(&mut parallel, bounds, upper_left, lower_right, 255, fractal);
+
+    assert_eq!(serial, parallel);
+}
+
+#[test]
+fn test_render_set_color_parallel_matches_serial_on_deep_zoom() {
+    let bounds = (101, 79);
+    let upper_left = Complex {
+        re: -0.743644786,
+        im: 0.1318259042,
+    };
+    let lower_right = Complex {
+        re: -0.743643786,
+        im: 0.1318249042,
+    };
+    let fractal = FractalKind::Mandelbrot;
+
+    let mut serial = vec![0; bounds.0 * bounds.1 * 3];
+    render_set_color(&mut serial, bounds, upper_left, lower_right, 5000, fractal);
+
+    let mut parallel = vec![0; bounds.0 * bounds.1 * 3];
+    render_set_color_parallel(&mut parallel, bounds, upper_left, lower_right, 5000, fractal);
+
+    assert_eq!(serial, parallel);
+}
+
+pub fn parse_complex(s: &str) -> Option<Complex<f64>> {
+    match parse_pair(s, ',') {
+        Some((re, im)) => Some(Complex { re, im }),
+        _ => None,
+    }
+}
+
+pub fn parse_pair<T: FromStr>(s: &str, separator: char) -> Option<(T, T)> {
+    match s.find(separator) {
+        None => None,
+        Some(index) => match (T::from_str(&s[..index]), T::from_str(&s[index + 1..])) {
+            (Ok(l), Ok(r)) => Some((l, r)),
+            _ => None,
+        },
+    }
+}
+
+#[test]
+fn test_parse_pair() {
+    assert_eq!(parse_pair::<i32>("  ", 'x'), None);
+    assert_eq!(parse_pair::<i32>("100x", 'x'), None);
+    assert_eq!(parse_pair::<i32>("x200", 'x'), None);
+    assert_eq!(parse_pair::<i32>("100x200bv", 'x'), None);
+    assert_eq!(parse_pair::<i32>("100x200", 'x'), Some((100, 200)));
+}
+
+pub fn pixel_to_point(
+    bounds: (usize, usize),
+    pixel: (usize, usize),
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+) -> Complex<f64> {
+    let (width, height) = (
+        lower_right.re - upper_left.re,
+        upper_left.im - lower_right.im,
+    );
+    Complex {
+        re: upper_left.re + pixel.0 as f64 * width / bounds.0 as f64,
+        im: upper_left.im - pixel.1 as f64 * height / bounds.1 as f64,
+    }
+}
+
+#[test]
+fn test_pixel_to_point() {
+    assert_eq!(
+        pixel_to_point(
+            (100, 200),
+            (25, 175),
+            Complex { re: -1.0, im: 1.0 },
+            Complex { re: 1.0, im: -1.0 }
+        ),
+        Complex {
+            re: -0.5,
+            im: -0.75
+        }
+    )
+}
+
+// the inverse of `pixel_to_point`: maps a complex point back to its pixel
+// coordinates, or `None` if the point falls outside `bounds`.
+fn point_to_pixel(
+    bounds: (usize, usize),
+    point: Complex<f64>,
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+) -> Option<(usize, usize)> {
+    let (width, height) = (
+        lower_right.re - upper_left.re,
+        upper_left.im - lower_right.im,
+    );
+    let col = (point.re - upper_left.re) / width * bounds.0 as f64;
+    let row = (upper_left.im - point.im) / height * bounds.1 as f64;
+
+    if col < 0.0 || row < 0.0 {
+        return None;
+    }
+
+    let (col, row) = (col as usize, row as usize);
+    if col >= bounds.0 || row >= bounds.1 {
+        return None;
+    }
+
+    Some((col, row))
+}
+
+#[test]
+fn test_point_to_pixel_round_trips_pixel_to_point() {
+    let bounds = (100, 200);
+    let upper_left = Complex { re: -1.0, im: 1.0 };
+    let lower_right = Complex { re: 1.0, im: -1.0 };
+
+    let point = pixel_to_point(bounds, (25, 175), upper_left, lower_right);
+    assert_eq!(
+        point_to_pixel(bounds, point, upper_left, lower_right),
+        Some((25, 175))
+    );
+}
+
+#[test]
+fn test_point_to_pixel_outside_view_is_none() {
+    let bounds = (100, 200);
+    let upper_left = Complex { re: -1.0, im: 1.0 };
+    let lower_right = Complex { re: 1.0, im: -1.0 };
+
+    assert_eq!(
+        point_to_pixel(bounds, Complex { re: -5.0, im: 0.0 }, upper_left, lower_right),
+        None
+    );
+    assert_eq!(
+        point_to_pixel(bounds, Complex { re: 0.0, im: 5.0 }, upper_left, lower_right),
+        None
+    );
+}
+
+// entry point for the browser demo: renders straight to a tightly packed
+// RGBA buffer that can be blitted into an HTML canvas `ImageData` without
+// any further conversion.
+//
+// vanilla `wasm32-unknown-unknown` has no threads, so the rayon-backed
+// renderer would panic there; wasm builds fall back to the serial
+// renderer instead. native builds (the CLI) keep using the parallel one.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn render_to_rgba(
+    width: usize,
+    height: usize,
+    ul_re: f64,
+    ul_im: f64,
+    lr_re: f64,
+    lr_im: f64,
+    limit: usize,
+) -> Vec<u8> {
+    let bounds = (width, height);
+    let upper_left = Complex { re: ul_re, im: ul_im };
+    let lower_right = Complex { re: lr_re, im: lr_im };
+
+    let mut rgb = vec![0u8; width * height * 3];
+
+    #[cfg(target_arch = "wasm32")]
+    render_set_color(&mut rgb, bounds, upper_left, lower_right, limit, FractalKind::Mandelbrot);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    render_set_color_parallel(
+        &mut rgb,
+        bounds,
+        upper_left,
+        lower_right,
+        limit,
+        FractalKind::Mandelbrot,
+    );
+
+    rgb.chunks(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect()
+}
+
+#[test]
+fn test_render_to_rgba_produces_tight_rgba_buffer() {
+    let pixels = render_to_rgba(10, 8, -1.20, 0.35, -1.0, 0.20, 100);
+    assert_eq!(pixels.len(), 10 * 8 * 4);
+}
@@ -1,147 +1,180 @@
-use std::{env, fs::File, str::FromStr};
+use std::{
+    env,
+    fs::File,
+    io::Write,
+    path::Path,
+    str::FromStr,
+};
 
 use image::{codecs::png::PngEncoder, ExtendedColorType, ImageEncoder};
-use num::Complex;
+
+use mandelbrot_set_plotter::{
+    parse_complex, parse_pair, render_buddhabrot, render_set_color_parallel, render_set_parallel,
+    FractalKind,
+};
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let raw_args: Vec<String> = env::args().collect();
+
+    let mut color = false;
+    let mut buddhabrot_samples: Option<usize> = None;
+    let mut format: Option<OutputFormat> = None;
+    let mut args: Vec<String> = vec![raw_args[0].clone()];
+
+    let mut i = 1;
+    while i < raw_args.len() {
+        match raw_args[i].as_str() {
+            "--color" => color = true,
+            "--buddhabrot" => {
+                i += 1;
+                let samples = raw_args
+                    .get(i)
+                    .expect("--buddhabrot requires a SAMPLES argument");
+                buddhabrot_samples =
+                    Some(samples.parse().expect("error parsing SAMPLES"));
+            }
+            "--format" => {
+                i += 1;
+                let value = raw_args.get(i).expect("--format requires a FORMAT argument");
+                format = Some(OutputFormat::from_str(value).expect("error parsing FORMAT"));
+            }
+            other => args.push(other.to_string()),
+        }
+        i += 1;
+    }
 
-    if args.len() != 5 {
-        eprintln!("Usage: {} FILE PIXELS UPPERLEFT LOWERRIGHT", args[0]);
+    if args.len() != 5 && args.len() != 6 {
         eprintln!(
-            "Example: {} mandel.png 1000x750 -1.20,0.35 -1,0.20",
+            "Usage: {} [--color] [--buddhabrot SAMPLES] [--format FORMAT] FILE PIXELS UPPERLEFT LOWERRIGHT [FRACTAL]",
             args[0]
         );
+        eprintln!(
+            "Example: {} --color mandel.png 1000x750 -1.20,0.35 -1,0.20 multibrot3",
+            args[0]
+        );
+        eprintln!("FRACTAL is one of mandelbrot, multibrot3, burning-ship (default: mandelbrot)");
+        eprintln!("FORMAT is one of png, pnm, raw (default: by FILE's extension, else png)");
         std::process::exit(1);
     }
 
     let bounds = parse_pair(&args[2], 'x').expect("error parsing image dimensions");
     let upper_left = parse_complex(&args[3]).expect("error parsing upper left corner point");
     let lower_right = parse_complex(&args[4]).expect("error parsing lower right corner point");
-    let mut pixels = vec![0; bounds.0 * bounds.1];
-
-    render_set(&mut pixels, bounds, upper_left, lower_right);
-
-    save_image(&args[1], &pixels, bounds).expect("error writing PNG file");
-}
+    let fractal = match args.get(5) {
+        Some(arg) => FractalKind::from_str(arg).expect("error parsing fractal kind"),
+        None => FractalKind::Mandelbrot,
+    };
+    let format = format.unwrap_or_else(|| OutputFormat::from_extension(&args[1]));
+    let limit = 255;
+
+    if let Some(samples) = buddhabrot_samples {
+        let hits = render_buddhabrot(bounds, upper_left, lower_right, samples, limit);
+        save_image(&args[1], &hits, bounds, false, format).expect("error writing image file");
+        return;
+    }
 
-fn render_set(
-    pixels: &mut [u8],
-    bounds: (usize, usize),
-    upper_left: Complex<f64>,
-    lower_right: Complex<f64>,
-) {
-    assert_eq!(pixels.len(), bounds.0 * bounds.1);
-
-    for row in 0..bounds.1 {
-        for col in 0..bounds.0 {
-            let point = pixel_to_point(bounds, (col, row), upper_left, lower_right);
-            pixels[row * bounds.0 + col] = match escape_time(point, 255) {
-                None => 0,
-                Some(time) => 255 - time as u8,
-            };
-        }
+    if color {
+        let mut pixels = vec![0; bounds.0 * bounds.1 * 3];
+        render_set_color_parallel(&mut pixels, bounds, upper_left, lower_right, limit, fractal);
+        save_image(&args[1], &pixels, bounds, true, format).expect("error writing image file");
+    } else {
+        let mut pixels = vec![0; bounds.0 * bounds.1];
+        render_set_parallel(&mut pixels, bounds, upper_left, lower_right, limit, fractal);
+        save_image(&args[1], &pixels, bounds, false, format).expect("error writing image file");
     }
 }
 
-fn save_image(filename: &str, pixels: &[u8], bounds: (usize, usize)) -> Result<(), std::io::Error> {
-    let output_file = File::create(filename)?;
-    let encoder = PngEncoder::new(output_file);
-    encoder
-        .write_image(
-            pixels,
-            bounds.0 as u32,
-            bounds.1 as u32,
-            ExtendedColorType::L8,
-        )
-        .unwrap();
-
-    Ok(())
+// output container selected via `--format` or, failing that, the FILE
+// extension; PNG remains the default when neither pins it down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Png,
+    Pnm,
+    Raw,
 }
 
-// this function checks whether a given complex number `c`
-// belongs to the Mandelbrot set using at most `limit` iterations.
-//
-// if `c` belongs to the Mandelbrot set, it returns `None`.
-// otherwise, the function returns `Some(i)` where `i` is the number of iterations
-// it took `c` to escape the circle of radius 2.
-fn escape_time(c: Complex<f64>, limit: usize) -> Option<usize> {
-    let mut z = Complex { re: 0.0, im: 0.0 };
-    for i in 0..limit {
-        if z.norm_sqr() > 4.0 {
-            return Some(i);
+impl OutputFormat {
+    fn from_extension(filename: &str) -> Self {
+        match Path::new(filename)
+            .extension()
+            .and_then(|ext| ext.to_str())
+        {
+            Some("pnm") | Some("ppm") | Some("pgm") => OutputFormat::Pnm,
+            Some("raw") => OutputFormat::Raw,
+            _ => OutputFormat::Png,
         }
-        z = z * z + c;
     }
-
-    None
 }
 
-#[test]
-fn test_escape_time() {
-    assert_eq!(escape_time(Complex { re: 0.0, im: 0.0 }, 10), None);
-    assert_eq!(escape_time(Complex { re: 0.25, im: 0.0 }, 10), None);
-    assert_eq!(escape_time(Complex { re: 0.5, im: 0.0 }, 10), Some(5));
-    assert_eq!(escape_time(Complex { re: 1.0, im: 0.0 }, 10), Some(3));
-    assert_eq!(escape_time(Complex { re: 0.0, im: 0.25 }, 10), None);
-    assert_eq!(escape_time(Complex { re: 0.0, im: 0.5 }, 10), None);
-    assert_eq!(escape_time(Complex { re: 0.0, im: 1.0 }, 10), None);
-}
+impl FromStr for OutputFormat {
+    type Err = String;
 
-fn parse_complex(s: &str) -> Option<Complex<f64>> {
-    match parse_pair(s, ',') {
-        Some((re, im)) => Some(Complex { re, im }),
-        _ => None,
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "png" => Ok(OutputFormat::Png),
+            "pnm" => Ok(OutputFormat::Pnm),
+            "raw" => Ok(OutputFormat::Raw),
+            _ => Err(format!("unknown output format '{s}'")),
+        }
     }
 }
 
-fn parse_pair<T: FromStr>(s: &str, separator: char) -> Option<(T, T)> {
-    match s.find(separator) {
-        None => None,
-        Some(index) => match (T::from_str(&s[..index]), T::from_str(&s[index + 1..])) {
-            (Ok(l), Ok(r)) => Some((l, r)),
-            _ => None,
-        },
+fn save_image(
+    filename: &str,
+    pixels: &[u8],
+    bounds: (usize, usize),
+    color: bool,
+    format: OutputFormat,
+) -> Result<(), std::io::Error> {
+    match format {
+        OutputFormat::Png => save_png(filename, pixels, bounds, color),
+        OutputFormat::Pnm => save_pnm(filename, pixels, bounds, color),
+        OutputFormat::Raw => save_raw(filename, pixels),
     }
 }
 
-#[test]
-fn test_parse_pair() {
-    assert_eq!(parse_pair::<i32>("  ", 'x'), None);
-    assert_eq!(parse_pair::<i32>("100x", 'x'), None);
-    assert_eq!(parse_pair::<i32>("x200", 'x'), None);
-    assert_eq!(parse_pair::<i32>("100x200bv", 'x'), None);
-    assert_eq!(parse_pair::<i32>("100x200", 'x'), Some((100, 200)));
+fn save_png(
+    filename: &str,
+    pixels: &[u8],
+    bounds: (usize, usize),
+    color: bool,
+) -> Result<(), std::io::Error> {
+    let output_file = File::create(filename)?;
+    let encoder = PngEncoder::new(output_file);
+    let color_type = if color {
+        ExtendedColorType::Rgb8
+    } else {
+        ExtendedColorType::L8
+    };
+    encoder
+        .write_image(pixels, bounds.0 as u32, bounds.1 as u32, color_type)
+        .unwrap();
+
+    Ok(())
 }
 
-fn pixel_to_point(
+// writes a binary PNM: P5 (grayscale) or P6 (RGB), depending on `color`.
+// the header is plain ASCII, the pixel data that follows is raw bytes --
+// no external codec needed, and the format pipes straight into other
+// Unix image tools (`pnmtopng`, `display`, ...).
+fn save_pnm(
+    filename: &str,
+    pixels: &[u8],
     bounds: (usize, usize),
-    pixel: (usize, usize),
-    upper_left: Complex<f64>,
-    lower_right: Complex<f64>,
-) -> Complex<f64> {
-    let (width, height) = (
-        lower_right.re - upper_left.re,
-        upper_left.im - lower_right.im,
-    );
-    Complex {
-        re: upper_left.re + pixel.0 as f64 * width / bounds.0 as f64,
-        im: upper_left.im - pixel.1 as f64 * height / bounds.1 as f64,
-    }
+    color: bool,
+) -> Result<(), std::io::Error> {
+    let mut output_file = File::create(filename)?;
+    let magic = if color { "P6" } else { "P5" };
+    write!(output_file, "{magic}\n{} {}\n255\n", bounds.0, bounds.1)?;
+    output_file.write_all(pixels)?;
+
+    Ok(())
 }
 
-#[test]
-fn test_pixel_to_point() {
-    assert_eq!(
-        pixel_to_point(
-            (100, 200),
-            (25, 175),
-            Complex { re: -1.0, im: 1.0 },
-            Complex { re: 1.0, im: -1.0 }
-        ),
-        Complex {
-            re: -0.5,
-            im: -0.75
-        }
-    )
+// dumps the pixel buffer as-is, with no header at all.
+fn save_raw(filename: &str, pixels: &[u8]) -> Result<(), std::io::Error> {
+    let mut output_file = File::create(filename)?;
+    output_file.write_all(pixels)?;
+
+    Ok(())
 }